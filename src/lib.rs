@@ -64,8 +64,11 @@ extern crate colored;
 
 use colored::*;
 use std::{
+    env,
     fmt::{self, Display},
-    process,
+    io::{self, IsTerminal, Write},
+    process::{self, ExitCode, Termination},
+    sync::atomic::{AtomicI32, Ordering},
 };
 
 /// `Text` is the main type that gets thrown around between functions and methods. It is basically a
@@ -73,7 +76,19 @@ use std::{
 /// some things for it.
 #[derive(Debug)]
 pub struct Text {
-    text: String,
+    label: ColoredString,
+    message: String,
+    cause: Option<Box<Text>>,
+    stream: Stream,
+}
+
+/// The output stream a `Text` prefers to be printed on. `success` and `info` default to
+/// `Stdout`; `warning`, `error` and `debug` default to `Stderr` since they're diagnostics rather
+/// than a program's actual output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
 }
 
 /// `Error` type is an alias of the `Text` type that we use to denote errors specifically. The fact
@@ -82,7 +97,7 @@ pub type Error = Text;
 
 impl std::fmt::Display for Text {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.text)
+        write!(f, "{}", self.line_for(self.stream))
     }
 }
 
@@ -101,11 +116,86 @@ impl Text {
         I: Display,
     {
         Self {
-            text: format!("{}{} {}", label, ":".bold(), message),
+            label,
+            message: format!("{}", message),
+            cause: None,
+            stream: Stream::Stdout,
+        }
+    }
+
+    /// Sets the stream this `Text` prefers to be printed on. Used internally by `error`, `warning`
+    /// and `debug` to route diagnostics to `stderr`.
+    fn on_stream(mut self, stream: Stream) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// Prepends `ctx` to this message, keeping the original message around as the `cause`. The new
+    /// head keeps the label (and colour) of the error it wraps, so a chain of `context` calls reads
+    /// as a stack of equally-labelled diagnostics rather than losing its label after the first call.
+    /// Chain several calls to build up a layered diagnostic, innermost cause last.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use idioma::*;
+    /// let err = error("file not found").context("could not open config");
+    /// assert_eq!(err.causes().count(), 1);
+    /// ```
+    pub fn context<D>(self, ctx: D) -> Error
+    where
+        D: Display,
+    {
+        Error {
+            label: self.label.clone(),
+            message: format!("{}", ctx),
+            stream: self.stream,
+            cause: Some(Box::new(self)),
         }
     }
 
-    /// Displays `Text` thanks to the `std::fmt::Display` trait.
+    /// Walks the chain of causes attached via `context`, starting with the most immediate one and
+    /// ending with the root cause. Does not include `self`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use idioma::*;
+    /// let err = error("disk is full")
+    ///     .context("could not write file")
+    ///     .context("backup failed");
+    /// let messages: Vec<String> = err.causes().map(|c| c.to_string()).collect();
+    /// assert_eq!(messages.len(), 2);
+    /// ```
+    pub fn causes(&self) -> impl Iterator<Item = &Text> {
+        std::iter::successors(self.cause.as_deref(), |text| text.cause.as_deref())
+    }
+
+    /// Renders just this `Text`'s own label and message, colorized (or not) for `stream`. Does not
+    /// include any `cause` chain - see `chain_for` for that.
+    fn line_for(&self, stream: Stream) -> String {
+        with_color_override(should_colorize(stream), || {
+            format!("{}{} {}", self.label, ":".bold(), self.message)
+        })
+    }
+
+    /// Renders this message together with its chain of causes, one indented "caused by:" line per
+    /// ancestor, colorized (or not) for `stream`. Unlike `Display`, which only ever shows this
+    /// `Text`'s own line, this is what `print`/`print_to` show - the chain is opt-in for whoever
+    /// asks for it.
+    fn chain_for(&self, stream: Stream) -> String {
+        let mut rendered = self.line_for(stream);
+        for (depth, cause) in self.causes().enumerate() {
+            let prefix = with_color_override(should_colorize(stream), || "caused by:".bold().to_string());
+            rendered.push_str(&format!("\n{}{} {}", "  ".repeat(depth + 1), prefix, cause.line_for(stream)));
+        }
+        rendered
+    }
+
+    /// Displays `Text` on its preferred stream (`stdout` for `success`/`info`, `stderr` for
+    /// `warning`/`error`/`debug`), together with its full chain of causes. Colors are applied or
+    /// stripped based on whether that exact stream is a terminal, honouring `NO_COLOR` and
+    /// `CLICOLOR_FORCE` besides.
     ///
     /// # Examples
     ///
@@ -114,7 +204,29 @@ impl Text {
     /// warning("This message is going to be printed out immediately!").print();
     /// ```
     pub fn print(&self) {
-        println!("{}", self)
+        self.print_to(self.stream)
+    }
+
+    /// Like `print`, but forces the message onto the given `stream` regardless of the `Text`'s own
+    /// preference.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use idioma::*;
+    /// success("Logged to stderr for once.").print_to(Stream::Stderr);
+    /// ```
+    pub fn print_to(&self, stream: Stream) {
+        let rendered = self.chain_for(stream);
+        let result = match stream {
+            Stream::Stdout => writeln!(io::stdout(), "{}", rendered),
+            Stream::Stderr => writeln!(io::stderr(), "{}", rendered),
+        };
+        if let Err(e) = result {
+            if e.kind() != io::ErrorKind::BrokenPipe {
+                panic!("idioma: failed to print message: {}", e);
+            }
+        }
     }
 
     /// Displays `message` and terminates the program via `std::process::exit`. Please note that
@@ -143,6 +255,54 @@ impl Text {
     }
 }
 
+/// Lets `Text` (and therefore `idioma::Error`) stand in for the exit status of `main` itself, the
+/// way `std::process::ExitCode` does. `report` prints the message to `stderr` and always yields a
+/// non-zero exit code, since `Text` on its own carries no notion of "success".
+///
+/// You won't usually name this impl directly - reach for `Terminator` below so that
+/// `fn main() -> Terminator` can return `Ok(())` as well as an error.
+impl Termination for Text {
+    fn report(self) -> ExitCode {
+        self.print_to(Stream::Stderr);
+        ExitCode::from(1)
+    }
+}
+
+/// A thin wrapper around `Result<(), idioma::Error>` that implements `std::process::Termination`,
+/// letting you write `main` the idiomatic `? in main` way while still getting idioma's coloured
+/// error output instead of the default `{:?}` dump.
+///
+/// # Examples
+///
+/// ```
+/// use idioma::*;
+///
+/// fn main() -> Terminator {
+///     run().into()
+/// }
+///
+/// fn run() -> Result<(), Error> {
+///     success("Yay, you actually managed to compile this!").print();
+///     Ok(())
+/// }
+/// ```
+pub struct Terminator(pub Result<(), Error>);
+
+impl Termination for Terminator {
+    fn report(self) -> ExitCode {
+        match self.0 {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => error.report(),
+        }
+    }
+}
+
+impl From<Result<(), Error>> for Terminator {
+    fn from(result: Result<(), Error>) -> Self {
+        Terminator(result)
+    }
+}
+
 /// Allows you to create and print messages with custom labels. Essentially, allows you to write
 /// your own functions like `error`, `info`, etc. that we already have here.
 ///
@@ -177,7 +337,8 @@ where
     Text::make("success".green().bold(), message)
 }
 
-/// Debug your code with style.
+/// Debug your code with style. Printed to `stderr`, since it's a diagnostic rather than program
+/// output.
 ///
 /// # Example
 ///
@@ -189,10 +350,10 @@ pub fn debug<D>(message: D) -> Text
 where
     D: Display,
 {
-    Text::make("debug".blue().bold(), message)
+    Text::make("debug".blue().bold(), message).on_stream(Stream::Stderr)
 }
 
-/// Displays a warning.
+/// Displays a warning. Printed to `stderr`, since it's a diagnostic rather than program output.
 ///
 /// # Example
 ///
@@ -204,7 +365,7 @@ pub fn warning<I>(message: I) -> Text
 where
     I: Display,
 {
-    Text::make("warning".yellow().bold(), message)
+    Text::make("warning".yellow().bold(), message).on_stream(Stream::Stderr)
 }
 
 /// Returns a neutral info message.
@@ -223,7 +384,8 @@ where
     Text::make("info".purple().bold(), message)
 }
 
-/// Returns a bright-red error message that draws attention.
+/// Returns a bright-red error message that draws attention. Printed to `stderr`, since it's a
+/// diagnostic rather than program output.
 ///
 /// # Example
 ///
@@ -235,7 +397,7 @@ pub fn error<I>(message: I) -> Error
 where
     I: Display,
 {
-    Text::make("error".red().bold(), message)
+    Text::make("error".red().bold(), message).on_stream(Stream::Stderr)
 }
 
 /// Use `into` to turn any `Result` type with a displayable error into `Result<O, idioma::Error>`.
@@ -253,6 +415,34 @@ where
     }
 }
 
+/// Lets you attach context to any `Result` in a single step, turning a foreign error straight into
+/// a `Result<O, idioma::Error>` whose cause chain remembers the original message.
+///
+/// # Examples
+///
+/// ```
+/// use idioma::*;
+/// use std::fs::File;
+///
+/// let result = File::open("non-existent.txt").context("could not open config");
+/// assert!(result.is_err());
+/// ```
+pub trait ResultExt<O> {
+    fn context<D: Display>(self, ctx: D) -> Result<O, Error>;
+}
+
+impl<O, E> ResultExt<O> for Result<O, E>
+where
+    E: Display,
+{
+    fn context<D: Display>(self, ctx: D) -> Result<O, Error> {
+        match self {
+            Ok(o) => Ok(o),
+            Err(e) => Err(error(e).context(ctx)),
+        }
+    }
+}
+
 /// Somethimes you get a `Result` and you want to continue execution as normal if case it's `Ok` or
 /// exit if it's `Err`. This function allows you to do precisely that.
 ///
@@ -274,3 +464,165 @@ pub fn exit_if_error<O>(result: Result<O, Error>) -> Result<O, Error> {
         Err(e) => Err(e.exit(1)),
     }
 }
+
+/// Process-global exit code, for tools that want to keep going after a recoverable problem but
+/// still fail at the end. Defaults to `0`.
+static EXIT_CODE: AtomicI32 = AtomicI32::new(0);
+
+/// Records that the process should eventually exit with `code`, unless a more severe `code` was
+/// already recorded - `set_exit_code` only ever raises the stored value, never lowers it, so an
+/// early fatal problem can't be masked by a later, milder one.
+///
+/// # Examples
+///
+/// ```
+/// use idioma::*;
+/// set_exit_code(1);
+/// set_exit_code(2);
+/// assert_eq!(get_exit_code(), 2);
+/// set_exit_code(1);
+/// assert_eq!(get_exit_code(), 2);
+/// ```
+pub fn set_exit_code(code: i32) {
+    EXIT_CODE.fetch_max(code, Ordering::SeqCst);
+}
+
+/// Returns the exit code accumulated so far via `set_exit_code`.
+pub fn get_exit_code() -> i32 {
+    EXIT_CODE.load(Ordering::SeqCst)
+}
+
+/// Runs `f` to completion, then terminates the process. On `Err`, prints the error and exits with
+/// code `1`. On `Ok`, exits with whatever code was accumulated via `set_exit_code` while `f` ran
+/// (or `0` if nothing raised it).
+///
+/// This lets a program keep processing after `warning`-level problems - calling `set_exit_code`
+/// along the way - and still report failure once everything is done.
+///
+/// # Examples
+///
+/// ```no_run
+/// use idioma::*;
+/// run(|| {
+///     for input in ["good", "bad"] {
+///         if input == "bad" {
+///             warning("skipping a bad input").print();
+///             set_exit_code(2);
+///         }
+///     }
+///     Ok(())
+/// });
+/// ```
+pub fn run(f: impl FnOnce() -> Result<(), Error>) -> ! {
+    match f() {
+        Ok(()) => process::exit(get_exit_code()),
+        Err(e) => {
+            e.print();
+            process::exit(1);
+        }
+    }
+}
+
+/// Decides whether messages printed to `stream` should keep their colors, following the same
+/// conventions as anstream: `CLICOLOR_FORCE` forces colors on, but only when set to something
+/// other than `0`; `NO_COLOR` disables them, but only when set to a non-empty value; otherwise we
+/// colorize only when `stream` is actually a terminal.
+fn should_colorize(stream: Stream) -> bool {
+    if matches!(env::var("CLICOLOR_FORCE").ok().as_deref(), Some(v) if v != "0") {
+        return true;
+    }
+    if env::var("NO_COLOR").is_ok_and(|v| !v.is_empty()) {
+        return false;
+    }
+    match stream {
+        Stream::Stdout => io::stdout().is_terminal(),
+        Stream::Stderr => io::stderr().is_terminal(),
+    }
+}
+
+/// Runs `f` with `colored`'s global override forced to `enable`, then sets the override back to
+/// the colorization value that was in effect before we touched it. An override stays set
+/// afterwards even if none was set before the call.
+///
+/// `colored::ColoredString`'s own `Display` impl decides whether to emit ANSI codes from a single
+/// process-wide flag that, left alone, is derived from `stdout`'s tty-ness - not from whichever
+/// stream we're actually about to write to. Overriding it for the duration of rendering is what
+/// lets `line_for`/`chain_for` colorize (or not) based on the real target stream instead. We save
+/// the effective value beforehand and set it back afterwards rather than unconditionally calling
+/// `unset_override`, so we don't clobber an override a host program set up for itself.
+fn with_color_override<R>(enable: bool, f: impl FnOnce() -> R) -> R {
+    let prior = colored::control::SHOULD_COLORIZE.should_colorize();
+    colored::control::set_override(enable);
+    let result = f();
+    colored::control::set_override(prior);
+    result
+}
+
+/// Like `error`, but accepts `format!`-style arguments and prepends `file!():line!()` to the
+/// message, userror-style. Handy for distinguishing a library-internal bug from a user-facing
+/// message - reach for the plain `error` function when the source location would just be noise.
+///
+/// # Examples
+///
+/// ```
+/// use idioma::*;
+/// let x = 7;
+/// error!("bad value {x}");
+/// ```
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => {
+        $crate::error(format!("{}:{}: {}", file!(), line!(), format!($($arg)*)))
+    };
+}
+
+/// Like `warning`, but accepts `format!`-style arguments and prepends `file!():line!()` to the
+/// message, userror-style.
+///
+/// # Examples
+///
+/// ```
+/// use idioma::*;
+/// let x = 7;
+/// warning!("suspicious value {x}");
+/// ```
+#[macro_export]
+macro_rules! warning {
+    ($($arg:tt)*) => {
+        $crate::warning(format!("{}:{}: {}", file!(), line!(), format!($($arg)*)))
+    };
+}
+
+/// Like `info`, but accepts `format!`-style arguments and prepends `file!():line!()` to the
+/// message, userror-style.
+///
+/// # Examples
+///
+/// ```
+/// use idioma::*;
+/// let x = 7;
+/// info!("reached checkpoint {x}");
+/// ```
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        $crate::info(format!("{}:{}: {}", file!(), line!(), format!($($arg)*)))
+    };
+}
+
+/// Like `debug`, but accepts `format!`-style arguments and prepends `file!():line!()` to the
+/// message, userror-style.
+///
+/// # Examples
+///
+/// ```
+/// use idioma::*;
+/// let x = 7;
+/// debug!("hit this branch with x = {x}");
+/// ```
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        $crate::debug(format!("{}:{}: {}", file!(), line!(), format!($($arg)*)))
+    };
+}